@@ -1,61 +1,73 @@
 use crate::span::Span;
 
+#[derive(Debug, Clone, Copy)]
 pub enum Vis {
     Priv,
     Pub(Span),
 }
 
+#[derive(Debug)]
 pub struct Id {
     pub span: Span,
     pub value: String,
 }
 
+#[derive(Debug)]
 pub enum PatternBody {
     Id(Id),
 }
 
+#[derive(Debug)]
 pub struct Pattern {
     pub span: Span,
     pub body: PatternBody,
 }
 
+#[derive(Debug)]
 pub enum TypeBody {
     Named(Id),
 }
 
+#[derive(Debug)]
 pub struct Type {
     pub span: Span,
     pub body: TypeBody,
 }
 
+#[derive(Debug)]
 pub struct Param {
     pub span: Span,
     pub name: Pattern,
     pub ty: Type,
 }
 
+#[derive(Debug)]
 pub struct Fn {
     pub vis: Vis,
     pub name: Id,
     pub params: Vec<Param>,
 }
 
+#[derive(Debug)]
 pub struct Alias {
     pub vis: Vis,
     pub new: Type,
     pub under: Type,
 }
 
+#[derive(Debug)]
 pub enum ItemBody {
     Alias(Alias),
     Fn(Fn),
 }
 
+#[derive(Debug)]
 pub struct Item {
     pub span: Span,
     pub body: ItemBody,
 }
 
+#[derive(Debug)]
 pub struct File {
     pub span: Span,
     pub items: Vec<Item>,
@@ -0,0 +1,163 @@
+use std::fmt::Write as _;
+
+use crate::lex::{COLOR_KEYWORD, COLOR_NUM, COLOR_PUNCT, COLOR_RESET};
+use crate::source_map::SourceMap;
+use crate::span::{ByteSpan, Pos};
+
+/// Severity of a [`Diagnostic`], controlling the color and word used in its header.
+#[derive(Clone, Copy, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Severity {
+    const fn color(self) -> &'static str {
+        match self {
+            Self::Error => COLOR_KEYWORD,
+            Self::Warning => COLOR_PUNCT,
+        }
+    }
+
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+        }
+    }
+}
+
+/// A diagnostic message together with the spans that explain it.
+///
+/// `labels[0]`, if present, is the primary span and is underlined with `^`; every later label is
+/// secondary and is underlined with `-`. Spans are in the global byte-offset space so a
+/// diagnostic can point into any file registered with the [`SourceMap`] it's rendered against.
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<(ByteSpan, String)>,
+}
+
+impl Diagnostic {
+    #[must_use]
+    pub const fn error(message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            message,
+            labels: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_label(mut self, span: ByteSpan, label: impl Into<String>) -> Self {
+        self.labels.push((span, label.into()));
+        self
+    }
+}
+
+/// Renders a [`Diagnostic`] against a [`SourceMap`] as a codespan-style multi-line report: a
+/// header naming the file and position of the primary span, then for each label a line-numbered
+/// gutter showing the offending source line(s) with an underline row beneath.
+#[must_use]
+pub fn render(source_map: &SourceMap, diagnostic: &Diagnostic) -> String {
+    let mut out = format!(
+        "{}{}{}: {}\n",
+        diagnostic.severity.color(),
+        diagnostic.severity.label(),
+        COLOR_RESET,
+        diagnostic.message
+    );
+    if let Some((span, _)) = diagnostic.labels.first() {
+        let (name, pos) = source_map.lookup(span.lo);
+        let _ = writeln!(out, "  --> {name}:{pos}");
+    }
+
+    let gutter_width = diagnostic
+        .labels
+        .iter()
+        .map(|(span, _)| source_map.lookup(span.hi).1 .0)
+        .max()
+        .map_or(1, |n| n.to_string().len());
+
+    for (i, (span, label)) in diagnostic.labels.iter().enumerate() {
+        let source = source_map.source(span.lo);
+        let lines: Vec<&str> = source.lines().collect();
+        let start = source_map.lookup(span.lo).1;
+        let end = source_map.lookup(span.hi).1;
+        render_label(&mut out, &lines, start, end, label, i == 0, gutter_width);
+    }
+    out
+}
+
+fn render_label(
+    out: &mut String,
+    lines: &[&str],
+    start: Pos,
+    end: Pos,
+    label: &str,
+    is_primary: bool,
+    gutter_width: usize,
+) {
+    let (underline_color, underline_char) = if is_primary {
+        (COLOR_KEYWORD, '^')
+    } else {
+        (COLOR_PUNCT, '-')
+    };
+    for row in start.0..=end.0 {
+        let Some(text) = lines.get(row - 1) else {
+            continue;
+        };
+        let start_col = if row == start.0 { start.1 } else { 1 };
+        let end_col = if row == end.0 { end.1 } else { text.len() + 1 };
+        let _ = writeln!(out, "{COLOR_NUM}{row:>gutter_width$}{COLOR_RESET} | {text}");
+        let _ = write!(
+            out,
+            "{} | {}{underline_color}{}{COLOR_RESET}",
+            " ".repeat(gutter_width),
+            " ".repeat(start_col.saturating_sub(1)),
+            underline_char
+                .to_string()
+                .repeat(end_col.saturating_sub(start_col).max(1))
+        );
+        if row == end.0 && !label.is_empty() {
+            let _ = write!(out, " {label}");
+        }
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source_map::SourceMap;
+    use crate::span::BytePos;
+
+    /// Regression test for a panic in [`SourceMap::lookup`]: an EOF error's point position is
+    /// already at a file's last valid offset, so widening it into a one-byte [`ByteSpan`] must
+    /// not walk off the end of the file's registered range.
+    #[test]
+    fn renders_eof_diagnostic_without_panicking() {
+        let mut source_map = SourceMap::new();
+        let base = source_map.add_file("test.cannon", "fn foo(a:");
+        let eof = BytePos(base.0 + "fn foo(a:".len());
+        let diagnostic = Diagnostic::error("unexpected EOF".into()).with_label(eof.into(), "");
+        let rendered = render(&source_map, &diagnostic);
+        assert!(rendered.contains("unexpected EOF"));
+    }
+
+    #[test]
+    fn renders_multi_line_span_with_an_underline_on_each_line() {
+        let mut source_map = SourceMap::new();
+        let base = source_map.add_file("test.cannon", "abc\ndef\nghi");
+        let span = ByteSpan {
+            lo: BytePos(base.0 + 1),
+            hi: BytePos(base.0 + 6),
+        };
+        let diagnostic = Diagnostic::error("multi-line".into()).with_label(span, "here");
+        let rendered = render(&source_map, &diagnostic);
+        assert!(rendered.contains(" | abc"));
+        assert!(rendered.contains(" | def"));
+        assert!(rendered.contains("here"));
+        assert!(rendered.matches('^').count() >= 2);
+    }
+}
@@ -1,15 +1,23 @@
 use std::io;
 
-use thiserror::Error;
+use thiserror::Error as ThisError;
 
-use crate::span::Pos;
+use crate::span::{ByteSpan, BytePos};
 
-#[derive(Error, Debug)]
-pub enum CannonError {
+#[derive(ThisError, Debug)]
+pub enum Error {
     #[error("unexpected EOF at {0}")]
-    Eof(Pos),
+    Eof(BytePos),
     #[error("error reading input file: {0}")]
     ReadError(#[from] io::Error),
     #[error("unexpected {0:?} at {1}")]
-    UnexpectedChar(char, Pos),
+    UnexpectedChar(char, BytePos),
+    #[error("expected {expected}, found {found} at {span}")]
+    UnexpectedToken {
+        expected: &'static str,
+        found: String,
+        span: ByteSpan,
+    },
+    #[error("unexpected end of group at {0}")]
+    UnexpectedEndOfGroup(ByteSpan),
 }
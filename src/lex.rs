@@ -1,25 +1,38 @@
 use core::fmt;
+use core::fmt::Write as _;
 use core::{fmt::Debug, iter::Peekable, ops::Deref};
 
 use unicode_xid::UnicodeXID;
 
 use crate::Error;
 use crate::{
-    span::{Pos, Span},
+    span::{BytePos, ByteSpan, Pos, Span},
 };
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum TokenType {
     Comment,
+    Float,
     Id,
     Num,
     Punct,
     String,
 }
 
+/// Whether a single-character [`TokenType::Punct`] token is directly followed, with no
+/// intervening whitespace, by a character [`is_punct_char`] accepts, proc-macro2-style. A punct
+/// directly followed by `/` or `.` is `Alone`. Meaningless for other token types. A run of
+/// `Joint` punct tokens can be reassembled into a multi-character operator; see [`match_punct`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spacing {
+    Joint,
+    Alone,
+}
+
 pub struct Token {
     pub ty: TokenType,
     pub body: String,
+    pub spacing: Spacing,
 }
 
 impl Token {
@@ -27,6 +40,15 @@ impl Token {
         Self {
             ty: TokenType::Comment,
             body: comment,
+            spacing: Spacing::Alone,
+        }
+    }
+
+    const fn float(float: String) -> Self {
+        Self {
+            ty: TokenType::Float,
+            body: float,
+            spacing: Spacing::Alone,
         }
     }
 
@@ -34,6 +56,7 @@ impl Token {
         Self {
             ty: TokenType::Id,
             body: id,
+            spacing: Spacing::Alone,
         }
     }
 
@@ -41,13 +64,15 @@ impl Token {
         Self {
             ty: TokenType::Num,
             body: num,
+            spacing: Spacing::Alone,
         }
     }
 
-    const fn punct(punct: String) -> Self {
+    const fn punct(punct: String, spacing: Spacing) -> Self {
         Self {
             ty: TokenType::Punct,
             body: punct,
+            spacing,
         }
     }
 
@@ -55,6 +80,7 @@ impl Token {
         Self {
             ty: TokenType::String,
             body: string,
+            spacing: Spacing::Alone,
         }
     }
 
@@ -78,7 +104,17 @@ impl Deref for Token {
     }
 }
 
-#[derive(Debug)]
+/// ANSI color codes shared by [`highlight`] and [`crate::diagnostic`].
+pub(crate) const COLOR_RESET: &str = "\x1B[0m";
+pub(crate) const COLOR_COMMENT: &str = "\x1B[34m";
+pub(crate) const COLOR_KEYWORD: &str = "\x1B[31m";
+pub(crate) const COLOR_ID: &str = "\x1B[37m";
+pub(crate) const COLOR_NUM: &str = "\x1B[36m";
+pub(crate) const COLOR_PUNCT: &str = "\x1B[33m";
+pub(crate) const COLOR_STRING: &str = "\x1B[32m";
+pub(crate) const COLOR_GROUP: &str = "\x1B[35m";
+
+#[derive(Debug, Eq, PartialEq)]
 pub enum GroupType {
     Paren,   // ()
     Bracket, // []
@@ -86,6 +122,14 @@ pub enum GroupType {
 }
 
 impl GroupType {
+    const fn open_char(&self) -> char {
+        match self {
+            Self::Paren => '(',
+            Self::Bracket => '[',
+            Self::Brace => '{',
+        }
+    }
+
     const fn end_char(&self) -> char {
         match self {
             Self::Paren => ')',
@@ -151,6 +195,9 @@ impl From<Group> for LexemeBody {
 
 pub struct Lexeme {
     pub span: Span,
+    /// This lexeme's span in the global byte-offset space, for error reporting via
+    /// [`crate::source_map::SourceMap`] regardless of which input file it came from.
+    pub byte_span: ByteSpan,
     pub body: LexemeBody,
 }
 
@@ -161,9 +208,10 @@ impl Debug for Lexeme {
 }
 
 impl Lexeme {
-    fn new(span: impl Into<Span>, body: impl Into<LexemeBody>) -> Self {
+    fn new(span: impl Into<Span>, byte_span: ByteSpan, body: impl Into<LexemeBody>) -> Self {
         Self {
             span: span.into(),
+            byte_span,
             body: body.into(),
         }
     }
@@ -177,99 +225,249 @@ impl Deref for Lexeme {
     }
 }
 
+/// The single-character punctuation that `do_group` lexes as a [`TokenType::Punct`] token and
+/// that counts as a following punct for [`Spacing`]. `/` (when it starts no comment) and `.`
+/// (after a number) are also lexed as punct tokens but are not in this set. `do_group` tags each
+/// punct with a [`Spacing`] instead of hand-assembling multi-character operators itself; see
+/// [`match_punct`] for how the parser reassembles them.
+const fn is_punct_char(c: char) -> bool {
+    matches!(c, '#' | ';' | '=' | ',' | ':' | '*' | '-' | '>')
+}
+
+/// Matches `expected` (e.g. `":"` or a multi-character operator like `"::"` or `"->"`) against a
+/// run of single-character [`TokenType::Punct`] lexemes starting at `lexemes[0]`, requiring
+/// [`Spacing::Joint`] between every character but the last so whitespace can't hide inside an
+/// operator. Returns how many lexemes the match spans, or `None` if `expected` isn't there.
+#[must_use]
+pub fn match_punct(lexemes: &[Lexeme], expected: &str) -> Option<usize> {
+    let mut chars = expected.chars();
+    let mut count = 0;
+    for lexeme in lexemes {
+        let Some(want) = chars.next() else { break };
+        let LexemeBody::Token(token) = &lexeme.body else {
+            return None;
+        };
+        if token.ty != TokenType::Punct || !token.body.chars().eq([want]) {
+            return None;
+        }
+        count += 1;
+        let is_last = chars.clone().next().is_none();
+        if !is_last && token.spacing != Spacing::Joint {
+            return None;
+        }
+    }
+    if chars.next().is_some() {
+        None
+    } else {
+        Some(count)
+    }
+}
+
+/// Scans a run of base-`radix` digits with optional `_` separators into `value`, advancing
+/// `pos`/`offset` over every consumed character including separators. Stops, without consuming
+/// it, at the first character that is neither a digit nor `_`. A doubled or trailing separator
+/// is `Error::UnexpectedChar`; an empty run is not an error here, since callers that require at
+/// least one digit check `value`'s length themselves.
+fn scan_digits(
+    file: &mut Peekable<impl Iterator<Item = char>>,
+    pos: &mut Pos,
+    offset: &mut usize,
+    radix: u32,
+    value: &mut String,
+) -> Result<(), Error> {
+    // Offset of the most recent `_`, captured before it's consumed, so a doubled or trailing
+    // separator error points at the separator itself rather than whatever follows it.
+    let mut last_separator: Option<usize> = None;
+    loop {
+        match file.peek() {
+            Some(&c) if c.is_digit(radix) => {
+                file.next();
+                value.push(c);
+                pos.1 += 1;
+                *offset += 1;
+                last_separator = None;
+            }
+            Some(&'_') => {
+                if last_separator.is_some() {
+                    Err(Error::UnexpectedChar('_', BytePos(*offset)))?;
+                }
+                last_separator = Some(*offset);
+                file.next();
+                pos.1 += 1;
+                *offset += 1;
+            }
+            _ => break,
+        }
+    }
+    if let Some(sep_offset) = last_separator {
+        Err(Error::UnexpectedChar('_', BytePos(sep_offset)))?;
+    }
+    Ok(())
+}
+
 #[allow(clippy::missing_errors_doc)]
 #[allow(clippy::missing_panics_doc)]
 #[allow(clippy::too_many_lines)]
 pub fn do_group(
     file: &mut Peekable<impl Iterator<Item = char>>,
     pos: &mut Pos,
+    offset: &mut usize,
     end_char: Option<char>,
 ) -> Result<Vec<Lexeme>, Error> {
     let mut result = Vec::new();
     loop {
         match file.next() {
             x if x == end_char => {
-                if x.is_some() {
+                if let Some(c) = x {
                     pos.1 += 1;
+                    *offset += c.len_utf8();
                 }
                 break;
             },
-            None => Err(Error::Eof(*pos))?,
-            Some(' ') => pos.1 += 1,
+            None => Err(Error::Eof(BytePos(*offset)))?,
+            Some(' ') => {
+                pos.1 += 1;
+                *offset += 1;
+            }
             Some('\n') => {
                 pos.0 += 1;
                 pos.1 = 1;
+                *offset += 1;
             }
             Some(x) if x.is_xid_start() || x == '_' => {
                 let mut id = x.to_string();
                 let start = *pos;
+                let start_offset = *offset;
                 pos.1 += 1;
+                *offset += x.len_utf8();
                 while let Some(x) = file.peek() {
                     if !x.is_xid_continue() { break; }
                     id.push(*x);
+                    *offset += x.len_utf8();
                     file.next();
                     pos.1 += 1;
                 }
-                result.push(Lexeme::new((start, *pos), Token::id(id)));
+                let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                result.push(Lexeme::new((start, *pos), byte_span, Token::id(id)));
             }
-            Some(x) if x.is_numeric() => {
-                let mut num = x.to_string();
+            Some(x) if x.is_ascii_digit() => {
                 let start = *pos;
+                let start_offset = *offset;
+                let mut value = x.to_string();
                 pos.1 += 1;
-                while let Some(x) = file.peek() {
-                    if !x.is_xid_continue() { break; }
-                    num.push(*x);
+                *offset += 1;
+                let mut is_float = false;
+
+                if x == '0' && matches!(file.peek(), Some('x' | 'o' | 'b')) {
+                    let radix_char = *file.peek().unwrap();
+                    let radix = match radix_char {
+                        'x' => 16,
+                        'o' => 8,
+                        _ => 2,
+                    };
                     file.next();
+                    value.push(radix_char);
                     pos.1 += 1;
+                    *offset += 1;
+                    let digits_start = value.len();
+                    scan_digits(file, pos, offset, radix, &mut value)?;
+                    if value.len() == digits_start {
+                        match file.peek() {
+                            Some(&c) => Err(Error::UnexpectedChar(c, BytePos(*offset)))?,
+                            None => Err(Error::Eof(BytePos(*offset)))?,
+                        }
+                    }
+                } else {
+                    scan_digits(file, pos, offset, 10, &mut value)?;
+
+                    if file.peek() == Some(&'.') {
+                        file.next();
+                        pos.1 += 1;
+                        *offset += 1;
+                        match file.peek() {
+                            Some(&'_') => Err(Error::UnexpectedChar('_', BytePos(*offset)))?,
+                            Some(&c) if c.is_ascii_digit() => {
+                                is_float = true;
+                                value.push('.');
+                                scan_digits(file, pos, offset, 10, &mut value)?;
+                            }
+                            // Not followed by a digit: the `.` isn't part of this number, so emit
+                            // the number scanned so far and the `.` as its own punct (`1.foo`
+                            // stays a number then a punct, not a malformed float).
+                            _ => {
+                                let num_end = Pos(pos.0, pos.1 - 1);
+                                let num_byte_span =
+                                    ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset - 1) };
+                                result.push(Lexeme::new((start, num_end), num_byte_span, Token::num(value)));
+                                let dot_spacing = if matches!(file.peek(), Some(&next) if is_punct_char(next)) {
+                                    Spacing::Joint
+                                } else {
+                                    Spacing::Alone
+                                };
+                                let dot_byte_span =
+                                    ByteSpan { lo: BytePos(*offset - 1), hi: BytePos(*offset) };
+                                result.push(Lexeme::new(
+                                    num_end,
+                                    dot_byte_span,
+                                    Token::punct(".".into(), dot_spacing),
+                                ));
+                                continue;
+                            }
+                        }
+                    }
+
+                    if matches!(file.peek(), Some('e' | 'E')) {
+                        let marker = *file.peek().unwrap();
+                        file.next();
+                        pos.1 += 1;
+                        *offset += 1;
+                        let mut exponent = marker.to_string();
+                        if let Some(&sign @ ('+' | '-')) = file.peek() {
+                            file.next();
+                            exponent.push(sign);
+                            pos.1 += 1;
+                            *offset += 1;
+                        }
+                        match file.peek() {
+                            Some(&'_') => Err(Error::UnexpectedChar('_', BytePos(*offset)))?,
+                            Some(&c) if c.is_ascii_digit() => {
+                                is_float = true;
+                                value.push_str(&exponent);
+                                scan_digits(file, pos, offset, 10, &mut value)?;
+                            }
+                            Some(&c) => Err(Error::UnexpectedChar(c, BytePos(*offset)))?,
+                            None => Err(Error::Eof(BytePos(*offset)))?,
+                        }
+                    }
                 }
-                result.push(Lexeme::new((start, *pos), Token::num(num)));
-            }
-            Some(c @ ('#' | ';' | '=')) => {
-                let start = *pos;
-                pos.1 += 1;
-                result.push(Lexeme::new((start, *pos), Token::punct(c.into())));
+
+                let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                let token = if is_float { Token::float(value) } else { Token::num(value) };
+                result.push(Lexeme::new((start, *pos), byte_span, token));
             }
-            Some(':') => {
+            Some(c) if is_punct_char(c) => {
                 let start = *pos;
+                let start_offset = *offset;
                 pos.1 += 1;
-                let punct = if file.peek() == Some(&':') {
-                    file.next();
-                    pos.1 += 1;
-                    "::".into()
+                *offset += c.len_utf8();
+                let spacing = if matches!(file.peek(), Some(&next) if is_punct_char(next)) {
+                    Spacing::Joint
                 } else {
-                    ":".into()
+                    Spacing::Alone
                 };
-                result.push(Lexeme::new((start, *pos), Token::punct(punct)));
-            }
-            Some('*') => {
-                let mut punct = String::from("-");
-                let start = *pos;
-                pos.1 += 1;
-                if let Some(c @ '=') = file.peek() {
-                    punct.push(*c);
-                    file.next();
-                    pos.1 += 1;
-                }
-                result.push(Lexeme::new((start, *pos), Token::punct(punct)));
-            }
-            Some('-') => {
-                let mut punct = String::from("-");
-                let start = *pos;
-                pos.1 += 1;
-                if let Some(c @ ('>' | '-' | '=')) = file.peek() {
-                    punct.push(*c);
-                    file.next();
-                    pos.1 += 1;
-                }
-                result.push(Lexeme::new((start, *pos), Token::punct(punct)));
+                let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                result.push(Lexeme::new((start, *pos), byte_span, Token::punct(c.into(), spacing)));
             }
             Some('/') => {
                 let start = *pos;
+                let start_offset = *offset;
                 pos.1 += 1;
+                *offset += 1;
                 if file.peek() == Some(&'/') {
                     let mut text = String::from("//");
                     file.next();
                     pos.1 += 1;
+                    *offset += 1;
                     while let Some(&c) = file.peek() {
                         if c == '\n' {
                             break;
@@ -277,130 +475,531 @@ pub fn do_group(
                         text.push(c);
                         file.next();
                         pos.1 += 1;
+                        *offset += c.len_utf8();
                     }
-                    result.push(Lexeme::new((start, *pos), Token::comment(text)));
+                    let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                    result.push(Lexeme::new((start, *pos), byte_span, Token::comment(text)));
                 } else if file.peek() == Some(&'*') {
-                    todo!("Multiline?");
+                    file.next();
+                    pos.1 += 1;
+                    *offset += 1;
+                    let mut text = String::from("/*");
+                    let mut depth = 1usize;
+                    loop {
+                        match file.next() {
+                            None => Err(Error::Eof(BytePos(*offset)))?,
+                            Some('\n') => {
+                                text.push('\n');
+                                pos.0 += 1;
+                                pos.1 = 1;
+                                *offset += 1;
+                            }
+                            Some('*') if file.peek() == Some(&'/') => {
+                                file.next();
+                                text.push_str("*/");
+                                pos.1 += 2;
+                                *offset += 2;
+                                depth -= 1;
+                                if depth == 0 {
+                                    break;
+                                }
+                            }
+                            Some('/') if file.peek() == Some(&'*') => {
+                                file.next();
+                                text.push_str("/*");
+                                pos.1 += 2;
+                                *offset += 2;
+                                depth += 1;
+                            }
+                            Some(c) => {
+                                text.push(c);
+                                pos.1 += 1;
+                                *offset += c.len_utf8();
+                            }
+                        }
+                    }
+                    let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                    result.push(Lexeme::new((start, *pos), byte_span, Token::comment(text)));
                 } else {
-                    result.push(Lexeme::new(start, Token::punct("/".into())));
+                    let spacing = if matches!(file.peek(), Some(&next) if is_punct_char(next)) {
+                        Spacing::Joint
+                    } else {
+                        Spacing::Alone
+                    };
+                    let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                    result.push(Lexeme::new(start, byte_span, Token::punct("/".into(), spacing)));
                 }
             }
             Some('"') => {
                 let start = *pos;
+                let start_offset = *offset;
                 pos.1 += 1;
+                *offset += 1;
                 let mut text = String::new();
                 loop {
-                    let Some(c) = file.next() else { Err(Error::Eof(*pos))? };
+                    let Some(c) = file.next() else { Err(Error::Eof(BytePos(*offset)))? };
                     if c == '\n' {
-                        Err(Error::UnexpectedChar(c, *pos))?;
+                        Err(Error::UnexpectedChar(c, BytePos(*offset)))?;
                     } else if c == '\\' {
-                        todo!();
+                        pos.1 += 1;
+                        *offset += 1;
+                        let Some(escape) = file.next() else { Err(Error::Eof(BytePos(*offset)))? };
+                        match escape {
+                            'n' | 't' | 'r' | '0' | '\\' | '"' => {
+                                pos.1 += 1;
+                                *offset += 1;
+                                text.push(match escape {
+                                    'n' => '\n',
+                                    't' => '\t',
+                                    'r' => '\r',
+                                    '0' => '\0',
+                                    '\\' => '\\',
+                                    '"' => '"',
+                                    _ => unreachable!(),
+                                });
+                            }
+                            'x' => {
+                                pos.1 += 1;
+                                *offset += 1;
+                                let mut hex = String::with_capacity(2);
+                                for _ in 0..2 {
+                                    let Some(h) = file.next() else { Err(Error::Eof(BytePos(*offset)))? };
+                                    if !h.is_ascii_hexdigit() {
+                                        Err(Error::UnexpectedChar(h, BytePos(*offset)))?;
+                                    }
+                                    pos.1 += 1;
+                                    *offset += 1;
+                                    hex.push(h);
+                                }
+                                let value = u8::from_str_radix(&hex, 16).expect("validated hex digits");
+                                text.push(value as char);
+                            }
+                            'u' => {
+                                pos.1 += 1;
+                                *offset += 1;
+                                let Some(open) = file.next() else { Err(Error::Eof(BytePos(*offset)))? };
+                                if open != '{' {
+                                    Err(Error::UnexpectedChar(open, BytePos(*offset)))?;
+                                }
+                                pos.1 += 1;
+                                *offset += 1;
+                                let mut hex = String::new();
+                                let value = loop {
+                                    let Some(h) = file.next() else { Err(Error::Eof(BytePos(*offset)))? };
+                                    if h == '}' {
+                                        pos.1 += 1;
+                                        *offset += 1;
+                                        break u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                                    }
+                                    if !h.is_ascii_hexdigit() || hex.len() >= 6 {
+                                        Err(Error::UnexpectedChar(h, BytePos(*offset)))?;
+                                    }
+                                    pos.1 += 1;
+                                    *offset += 1;
+                                    hex.push(h);
+                                };
+                                let Some(ch) = value else { Err(Error::UnexpectedChar('u', BytePos(*offset)))? };
+                                text.push(ch);
+                            }
+                            other => Err(Error::UnexpectedChar(other, BytePos(*offset)))?,
+                        }
                     } else if c == '"' {
                         break;
                     } else {
                         pos.1 += 1;
+                        *offset += c.len_utf8();
                         text.push(c);
                     }
                 }
                 pos.1 += 1;
-                result.push(Lexeme::new((start, *pos), Token::string(text)));
+                *offset += 1;
+                let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                result.push(Lexeme::new((start, *pos), byte_span, Token::string(text)));
             }
             Some(x) if let Some(ty) = GroupType::from_start_char(x) => {
                 let start = *pos;
+                let start_offset = *offset;
                 pos.1 += 1;
-                let inner = do_group(file, pos, Some(ty.end_char()))?;
-                result.push(Lexeme::new((start, *pos), ty.build(inner)));
+                *offset += 1;
+                let inner = do_group(file, pos, offset, Some(ty.end_char()))?;
+                let byte_span = ByteSpan { lo: BytePos(start_offset), hi: BytePos(*offset) };
+                result.push(Lexeme::new((start, *pos), byte_span, ty.build(inner)));
             }
-            Some(x) => Err(Error::UnexpectedChar(x, *pos))?,
+            Some(x) => Err(Error::UnexpectedChar(x, BytePos(*offset)))?,
         }
     }
     Ok(result)
 }
 
+/// Lexes a file's characters into a token stream, starting its byte offsets at `base` so spans
+/// remain unique across every file registered with the [`crate::source_map::SourceMap`] that
+/// produced `base`.
 #[allow(clippy::missing_errors_doc)]
-pub fn lex(file: impl Iterator<Item = char>) -> Result<Vec<Lexeme>, Error> {
-    do_group(&mut file.peekable(), &mut Pos(1, 1), None)
+pub fn lex(file: impl Iterator<Item = char>, base: BytePos) -> Result<Vec<Lexeme>, Error> {
+    let mut offset = base.0;
+    do_group(&mut file.peekable(), &mut Pos(1, 1), &mut offset, None)
 }
 
-#[must_use]
-fn highlight_group(pos: &mut Pos, group: &[Lexeme]) -> String {
-    let mut result = String::new();
+/// A destination for [`highlight_group`]'s token stream, so the same whitespace-reconstruction
+/// walk over spans can drive more than one output format (ANSI terminal codes, HTML markup, ...).
+pub trait HighlightSink {
+    fn token(&mut self, ty: TokenType, is_keyword: bool, text: &str);
+    fn group_open(&mut self, ty: &GroupType);
+    fn group_close(&mut self, ty: &GroupType);
+    /// Whitespace (spaces and/or newlines) reconstructed between two lexemes' spans.
+    fn gap(&mut self, text: &str);
+}
+
+fn highlight_group(pos: &mut Pos, group: &[Lexeme], sink: &mut impl HighlightSink) {
     for lexeme in group {
         let start = lexeme.span.start;
+        let mut gap = String::new();
         while *pos != start {
             if pos.0 < start.0 {
                 pos.1 = 1;
                 pos.0 += 1;
-                result += "\n";
+                gap.push('\n');
             } else if pos.1 < start.1 {
                 pos.1 += 1;
-                result += " ";
+                gap.push(' ');
             } else {
                 panic!("invalid span");
             }
         }
+        if !gap.is_empty() {
+            sink.gap(&gap);
+        }
         match &lexeme.body {
             LexemeBody::Token(token) => {
-                match token.ty {
-                    TokenType::Comment => result += "\x1B[34m",
-                    TokenType::Id => {
-                        if token.is_keyword() {
-                            result += "\x1B[31m";
-                        } else {
-                            result += "\x1B[37m";
-                        }
-                    }
-                    TokenType::Num => result += "\x1B[36m",
-                    TokenType::Punct => result += "\x1B[33m",
-                    TokenType::String => result += "\x1B[32m",
-                }
+                sink.token(token.ty, token.is_keyword(), &token.body);
                 if token.ty == TokenType::String {
-                    result.push('"');
-                    result += &token.body;
-                    result.push('"');
                     pos.1 += token.body.len() + 2;
                 } else {
-                    result += &token.body;
                     pos.1 += token.body.len();
                 }
             }
             LexemeBody::Group(group) => {
-                result += "\x1B[35m";
-                match group.ty {
-                    GroupType::Brace => result += "{",
-                    GroupType::Bracket => result += "[",
-                    GroupType::Paren => result += "(",
-                }
+                sink.group_open(&group.ty);
                 pos.1 += 1;
-                result += &highlight_group(pos, &group.body);
+                highlight_group(pos, &group.body, sink);
                 let mut end = lexeme.span.end;
                 end.1 -= 1; // One column backward, since the span is inclusive
+                let mut gap = String::new();
                 while *pos != end {
                     if pos.0 < end.0 {
                         pos.1 = 1;
                         pos.0 += 1;
-                        result += "\n";
+                        gap.push('\n');
                     } else if pos.1 < end.1 {
                         pos.1 += 1;
-                        result += " ";
+                        gap.push(' ');
                     } else {
                         panic!("invalid span");
                     }
                 }
-                result += "\x1B[35m";
-                match group.ty {
-                    GroupType::Brace => result += "}",
-                    GroupType::Bracket => result += "]",
-                    GroupType::Paren => result += ")",
+                if !gap.is_empty() {
+                    sink.gap(&gap);
                 }
+                sink.group_close(&group.ty);
                 pos.1 += 1;
             }
         }
     }
-    result
+}
+
+/// Renders ANSI terminal escape codes, the historical (and still default) behavior of
+/// [`highlight`].
+pub struct AnsiSink {
+    out: String,
+}
+
+impl AnsiSink {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self { out: String::new() }
+    }
+
+    #[must_use]
+    pub fn finish(self) -> String {
+        self.out + COLOR_RESET
+    }
+}
+
+impl Default for AnsiSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HighlightSink for AnsiSink {
+    fn token(&mut self, ty: TokenType, is_keyword: bool, text: &str) {
+        self.out += match ty {
+            TokenType::Comment => COLOR_COMMENT,
+            TokenType::Float | TokenType::Num => COLOR_NUM,
+            TokenType::Id if is_keyword => COLOR_KEYWORD,
+            TokenType::Id => COLOR_ID,
+            TokenType::Punct => COLOR_PUNCT,
+            TokenType::String => COLOR_STRING,
+        };
+        if ty == TokenType::String {
+            self.out.push('"');
+            self.out += text;
+            self.out.push('"');
+        } else {
+            self.out += text;
+        }
+    }
+
+    fn group_open(&mut self, ty: &GroupType) {
+        self.out += COLOR_GROUP;
+        self.out.push(ty.open_char());
+    }
+
+    fn group_close(&mut self, ty: &GroupType) {
+        self.out += COLOR_GROUP;
+        self.out.push(ty.end_char());
+    }
+
+    fn gap(&mut self, text: &str) {
+        self.out += text;
+    }
+}
+
+/// Renders `<span class="tok-...">` markup wrapped in its own `<pre>` element, for embedding
+/// syntax-highlighted source in a web page.
+pub struct HtmlSink {
+    out: String,
+}
+
+impl HtmlSink {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            out: String::from("<pre>"),
+        }
+    }
+
+    #[must_use]
+    pub fn finish(mut self) -> String {
+        self.out += "</pre>";
+        self.out
+    }
+}
+
+impl Default for HtmlSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn escape_html(text: &str, out: &mut String) {
+    for c in text.chars() {
+        match c {
+            '&' => *out += "&amp;",
+            '<' => *out += "&lt;",
+            '>' => *out += "&gt;",
+            '"' => *out += "&quot;",
+            _ => out.push(c),
+        }
+    }
+}
+
+impl HighlightSink for HtmlSink {
+    fn token(&mut self, ty: TokenType, is_keyword: bool, text: &str) {
+        let class = match ty {
+            TokenType::Comment => "tok-comment",
+            TokenType::Float => "tok-float",
+            TokenType::Id => "tok-id",
+            TokenType::Num => "tok-num",
+            TokenType::Punct => "tok-punct",
+            TokenType::String => "tok-string",
+        };
+        let _ = write!(self.out, "<span class=\"{class}{}\">", if is_keyword { " kw" } else { "" });
+        if ty == TokenType::String {
+            self.out.push('"');
+            escape_html(text, &mut self.out);
+            self.out.push('"');
+        } else {
+            escape_html(text, &mut self.out);
+        }
+        self.out += "</span>";
+    }
+
+    fn group_open(&mut self, ty: &GroupType) {
+        let _ = write!(self.out, "<span class=\"tok-group\">{}</span>", ty.open_char());
+    }
+
+    fn group_close(&mut self, ty: &GroupType) {
+        let _ = write!(self.out, "<span class=\"tok-group\">{}</span>", ty.end_char());
+    }
+
+    fn gap(&mut self, text: &str) {
+        escape_html(text, &mut self.out);
+    }
 }
 
 #[must_use]
 pub fn highlight(file: &[Lexeme]) -> String {
-    highlight_group(&mut Pos(1, 1), file) + "\x1B[0m"
+    let mut sink = AnsiSink::new();
+    highlight_group(&mut Pos(1, 1), file, &mut sink);
+    sink.finish()
+}
+
+#[must_use]
+pub fn highlight_html(file: &[Lexeme]) -> String {
+    let mut sink = HtmlSink::new();
+    highlight_group(&mut Pos(1, 1), file, &mut sink);
+    sink.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lex_ok(src: &str) -> Vec<Lexeme> {
+        lex(src.chars(), BytePos(0)).expect("lex should succeed")
+    }
+
+    fn token_bodies(lexemes: &[Lexeme]) -> Vec<(TokenType, &str)> {
+        lexemes
+            .iter()
+            .map(|lexeme| match &lexeme.body {
+                LexemeBody::Token(token) => (token.ty, token.body.as_str()),
+                LexemeBody::Group(_) => panic!("expected a token, found a group"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nested_block_comments_track_depth() {
+        let lexemes = lex_ok("/* outer /* inner */ still outer */ 1");
+        assert_eq!(
+            token_bodies(&lexemes),
+            vec![
+                (TokenType::Comment, "/* outer /* inner */ still outer */"),
+                (TokenType::Num, "1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unterminated_block_comment_is_eof() {
+        assert!(matches!(
+            lex("/* never closed".chars(), BytePos(0)),
+            Err(Error::Eof(_))
+        ));
+    }
+
+    #[test]
+    fn string_escapes_decode_each_form() {
+        let lexemes = lex_ok(r#""\n\t\r\0\\\"\x41\u{1F600}""#);
+        let [Lexeme { body: LexemeBody::Token(token), .. }] = lexemes.as_slice() else {
+            panic!("expected a single string token");
+        };
+        assert_eq!(token.ty, TokenType::String);
+        assert_eq!(token.body, "\n\t\r\0\\\"A\u{1F600}");
+    }
+
+    #[test]
+    fn malformed_hex_escape_errors() {
+        assert!(matches!(
+            lex(r#""\xZZ""#.chars(), BytePos(0)),
+            Err(Error::UnexpectedChar('Z', _))
+        ));
+    }
+
+    #[test]
+    fn malformed_unicode_escape_errors() {
+        assert!(matches!(
+            lex(r#""\u{110000}""#.chars(), BytePos(0)),
+            Err(Error::UnexpectedChar('u', _))
+        ));
+    }
+
+    #[test]
+    fn unknown_escape_errors() {
+        assert!(matches!(
+            lex(r#""\q""#.chars(), BytePos(0)),
+            Err(Error::UnexpectedChar('q', _))
+        ));
+    }
+
+    #[test]
+    fn digit_separators_are_stripped_from_decimal_value() {
+        assert_eq!(token_bodies(&lex_ok("1_000")), vec![(TokenType::Num, "1000")]);
+    }
+
+    #[test]
+    fn hex_radix_prefix_is_kept_in_value() {
+        assert_eq!(token_bodies(&lex_ok("0xFF")), vec![(TokenType::Num, "0xFF")]);
+    }
+
+    #[test]
+    fn dot_not_followed_by_digit_splits_into_number_and_punct() {
+        assert_eq!(
+            token_bodies(&lex_ok("1.foo")),
+            vec![
+                (TokenType::Num, "1"),
+                (TokenType::Punct, "."),
+                (TokenType::Id, "foo"),
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_exponent_is_a_float() {
+        assert_eq!(token_bodies(&lex_ok("1e-3")), vec![(TokenType::Float, "1e-3")]);
+    }
+
+    #[test]
+    fn doubled_separator_error_points_at_the_second_underscore() {
+        match lex("1__2".chars(), BytePos(0)) {
+            Err(Error::UnexpectedChar('_', BytePos(offset))) => assert_eq!(offset, 2),
+            other => panic!("expected UnexpectedChar('_', BytePos(2)), got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn trailing_separator_error_points_at_the_separator_not_past_it() {
+        match lex("1_".chars(), BytePos(0)) {
+            Err(Error::UnexpectedChar('_', BytePos(offset))) => assert_eq!(offset, 1),
+            other => panic!("expected UnexpectedChar('_', BytePos(1)), got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn star_equals_lexes_as_two_joint_puncts_not_a_doubled_dash() {
+        // Regression test for the historical bug where the `'*'` arm built its token body from
+        // `"-"` instead of `"*"`.
+        let lexemes = lex_ok("*=");
+        assert_eq!(
+            token_bodies(&lexemes),
+            vec![(TokenType::Punct, "*"), (TokenType::Punct, "=")]
+        );
+        assert_eq!(match_punct(&lexemes, "*="), Some(2));
+    }
+
+    #[test]
+    fn arrow_operator_reassembles_from_joint_puncts() {
+        let lexemes = lex_ok("->");
+        assert_eq!(
+            token_bodies(&lexemes),
+            vec![(TokenType::Punct, "-"), (TokenType::Punct, ">")]
+        );
+        assert_eq!(match_punct(&lexemes, "->"), Some(2));
+    }
+
+    #[test]
+    fn whitespace_between_puncts_prevents_match_punct() {
+        let lexemes = lex_ok(": :");
+        assert_eq!(match_punct(&lexemes, "::"), None);
+    }
+
+    #[test]
+    fn highlight_html_escapes_reserved_characters_in_a_string_token() {
+        let lexemes = lex_ok(r#""<a & b>""#);
+        let html = highlight_html(&lexemes);
+        assert!(html.contains("&lt;a &amp; b&gt;"));
+        assert!(!html.contains("<a & b>"));
+    }
 }
@@ -2,13 +2,28 @@
 #![warn(clippy::nursery, clippy::pedantic)]
 #![feature(if_let_guard)]
 
+pub mod ast;
+pub mod diagnostic;
+pub mod error;
 pub mod lex;
+pub mod parse;
+pub mod source_map;
 pub mod span;
 
-use std::{path::{PathBuf, Path}, process, sync::RwLock, io};
+use std::{path::{PathBuf, Path}, process, sync::RwLock};
 
-use clap::{error::ErrorKind, CommandFactory, Parser};
-use span::Pos;
+use clap::{error::ErrorKind, CommandFactory, Parser, ValueEnum};
+use diagnostic::Diagnostic;
+use source_map::SourceMap;
+
+pub use error::Error;
+
+/// Output format for `--highlight-only`.
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Ansi,
+    Html,
+}
 
 /// Official compiler for the Cannon programming language
 #[derive(Parser)]
@@ -28,30 +43,44 @@ struct Options {
     /// Highlight only, don't compile
     #[arg(long)]
     highlight_only: bool,
+
+    /// Output format for `--highlight-only`
+    #[arg(long, value_enum, default_value = "ansi")]
+    format: Format,
 }
 
-static CURRENT_FILE: RwLock<String> = RwLock::new(String::new());
+static SOURCE_MAP: RwLock<SourceMap> = RwLock::new(SourceMap::new());
 
 fn main() {
     if let Err(e) = run_frontend() {
-        let file_text = CURRENT_FILE.read().unwrap();
-        let file_text = file_text.clone();
-        let file_lines: Vec<_> = file_text.lines().collect();
-        match e {
-            Error::Eof(pos) => {
-                println!("{}", file_lines[pos.0 - 1]);
-                println!("{}^ unexpected EOF", " ".repeat(pos.1 - 1));
-            }
-            Error::UnexpectedChar(c, pos) => {
-                println!("{}", file_lines[pos.0 - 1]);
-                println!("{}^ unexpected {c:?}", " ".repeat(pos.1 - 1));
-            }
-            Error::ReadError(_) => eprintln!("{e}"),
+        if let Error::ReadError(_) = e {
+            eprintln!("{e}");
+        } else {
+            let source_map = SOURCE_MAP.read().unwrap();
+            println!("{}", diagnostic::render(&source_map, &diagnostic_for(&e)));
         }
         process::exit(1);
     }
 }
 
+fn diagnostic_for(e: &Error) -> Diagnostic {
+    match *e {
+        Error::Eof(pos) => Diagnostic::error("unexpected EOF".into()).with_label(pos.into(), ""),
+        Error::UnexpectedChar(c, pos) => {
+            Diagnostic::error(format!("unexpected {c:?}")).with_label(pos.into(), "")
+        }
+        Error::UnexpectedToken {
+            expected,
+            ref found,
+            span,
+        } => Diagnostic::error(format!("expected {expected}, found {found}")).with_label(span, ""),
+        Error::UnexpectedEndOfGroup(span) => {
+            Diagnostic::error("unexpected end of group".into()).with_label(span, "")
+        }
+        Error::ReadError(_) => unreachable!("handled before diagnostic_for is called"),
+    }
+}
+
 fn run_frontend() -> Result<(), Error> {
     let options = Options::parse();
     if options.output.is_some() && options.files.len() > 1 && options.compile_only {
@@ -63,22 +92,7 @@ fn run_frontend() -> Result<(), Error> {
             .exit();
     }
     if options.compile_only {
-        for file in &options.files {
-            let output = options.output.clone().unwrap_or_else(|| {
-                file.strip_suffix(".cannon").unwrap_or(file).to_string() + ".o"
-            });
-            let file = PathBuf::from(file);
-            let output = PathBuf::from(output);
-            if !file.exists() {
-                Options::command()
-                    .error(
-                        ErrorKind::Io,
-                        &format!("file `{}` not found", file.display()),
-                    )
-                    .exit();
-            }
-            compile(&file, &output)?;
-        }
+        compile(&options.files)?;
     }
     if options.highlight_only {
         for file in &options.files {
@@ -95,34 +109,45 @@ fn run_frontend() -> Result<(), Error> {
                     )
                     .exit();
             }
-            highlight(&file, &output)?;
+            highlight(&file, &output, options.format)?;
         }
     }
     Ok(())
 }
 
-fn compile(file: &Path, _output: &Path) -> Result<(), Error> {
-    let file_str = std::fs::read_to_string(file)?;
-    *CURRENT_FILE.write().unwrap() = file_str.clone();
-    let lexed = lex::lex(file_str.chars())?;
-    println!("{lexed:#?}");
+/// Lexes and parses every input file in a single pass, sharing one [`SOURCE_MAP`] so a span from
+/// any of them can still be traced back to its own `file.cannon:row:col`.
+fn compile(files: &[String]) -> Result<(), Error> {
+    for file in files {
+        let path = PathBuf::from(file);
+        if !path.exists() {
+            Options::command()
+                .error(
+                    ErrorKind::Io,
+                    &format!("file `{}` not found", path.display()),
+                )
+                .exit();
+        }
+        let file_str = std::fs::read_to_string(&path)?;
+        let base = SOURCE_MAP.write().unwrap().add_file(file.clone(), file_str.clone());
+        let lexed = lex::lex(file_str.chars(), base)?;
+        let parsed = parse::parse_file(&lexed)?;
+        println!("{parsed:#?}");
+    }
     Ok(())
 }
 
-fn highlight(file: &Path, _output: &Path) -> Result<(), Error> {
+fn highlight(file: &Path, _output: &Path, format: Format) -> Result<(), Error> {
     let file_str = std::fs::read_to_string(file)?;
-    *CURRENT_FILE.write().unwrap() = file_str.clone();
-    let lexed = lex::lex(file_str.chars())?;
-    println!("{}", lex::highlight(&lexed));
+    let base = SOURCE_MAP
+        .write()
+        .unwrap()
+        .add_file(file.display().to_string(), file_str.clone());
+    let lexed = lex::lex(file_str.chars(), base)?;
+    let rendered = match format {
+        Format::Ansi => lex::highlight(&lexed),
+        Format::Html => lex::highlight_html(&lexed),
+    };
+    println!("{rendered}");
     Ok(())
 }
-
-#[derive(thiserror::Error, Debug)]
-pub enum Error {
-    #[error("unexpected EOF at {0}")]
-    Eof(Pos),
-    #[error("error reading input file: {0}")]
-    ReadError(#[from] io::Error),
-    #[error("unexpected {0:?} at {1}")]
-    UnexpectedChar(char, Pos),
-}
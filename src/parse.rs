@@ -0,0 +1,351 @@
+use crate::ast::{
+    Alias, File, Fn, Id, Item, ItemBody, Param, Pattern, PatternBody, Type, TypeBody, Vis,
+};
+use crate::lex::{match_punct, GroupType, Lexeme, LexemeBody, Token, TokenType};
+use crate::span::{ByteSpan, BytePos, Pos, Span};
+use crate::Error;
+
+/// Parses a fully lexed token stream into a [`File`].
+///
+/// Groups produced by [`crate::lex::do_group`] are already delimiter-matched, so the parser
+/// never has to balance parens or braces itself: a [`LexemeBody::Group`] is treated as a single
+/// pre-delimited subtree and recursed into.
+#[allow(clippy::missing_errors_doc)]
+pub fn parse_file(lexemes: &[Lexeme]) -> Result<File, Error> {
+    let span = span_of(lexemes);
+    let mut cursor = Cursor::new(lexemes);
+    let mut items = Vec::new();
+    while cursor.peek().is_some() {
+        items.push(parse_item(&mut cursor)?);
+    }
+    Ok(File { span, items })
+}
+
+fn span_of(lexemes: &[Lexeme]) -> Span {
+    match (lexemes.first(), lexemes.last()) {
+        (Some(first), Some(last)) => Span {
+            start: first.span.start,
+            end: last.span.end,
+        },
+        _ => Pos(1, 1).into(),
+    }
+}
+
+fn group_description(ty: &GroupType) -> &'static str {
+    match ty {
+        GroupType::Paren => "a `(...)` group",
+        GroupType::Bracket => "a `[...]` group",
+        GroupType::Brace => "a `{...}` group",
+    }
+}
+
+struct Cursor<'a> {
+    lexemes: &'a [Lexeme],
+    idx: usize,
+    end_offset: BytePos,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(lexemes: &'a [Lexeme]) -> Self {
+        Self {
+            end_offset: lexemes
+                .last()
+                .map_or(BytePos(0), |lexeme| lexeme.byte_span.hi),
+            lexemes,
+            idx: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&'a Lexeme> {
+        self.lexemes.get(self.idx)
+    }
+
+    fn next(&mut self) -> Option<&'a Lexeme> {
+        let lexeme = self.lexemes.get(self.idx)?;
+        self.idx += 1;
+        Some(lexeme)
+    }
+
+    /// The lexemes not yet consumed, for matching multi-character operators via
+    /// [`crate::lex::match_punct`] without committing to a consumption until it succeeds.
+    fn remaining(&self) -> &'a [Lexeme] {
+        &self.lexemes[self.idx..]
+    }
+
+    /// The byte span just past the last lexeme, for errors at an exhausted stream.
+    fn eof_byte_span(&self) -> ByteSpan {
+        self.end_offset.into()
+    }
+
+    fn next_token(&mut self, expected: &'static str) -> Result<(&'a Token, Span, ByteSpan), Error> {
+        let lexeme = self
+            .next()
+            .ok_or_else(|| Error::UnexpectedEndOfGroup(self.eof_byte_span()))?;
+        match &lexeme.body {
+            LexemeBody::Token(token) => Ok((token, lexeme.span, lexeme.byte_span)),
+            LexemeBody::Group(group) => Err(Error::UnexpectedToken {
+                expected,
+                found: group_description(&group.ty).into(),
+                span: lexeme.byte_span,
+            }),
+        }
+    }
+
+    fn next_group(
+        &mut self,
+        expected: &'static str,
+        ty: GroupType,
+    ) -> Result<(&'a [Lexeme], Span, ByteSpan), Error> {
+        let lexeme = self
+            .next()
+            .ok_or_else(|| Error::UnexpectedEndOfGroup(self.eof_byte_span()))?;
+        match &lexeme.body {
+            LexemeBody::Group(group) if group.ty == ty => {
+                Ok((&group.body, lexeme.span, lexeme.byte_span))
+            }
+            LexemeBody::Group(group) => Err(Error::UnexpectedToken {
+                expected,
+                found: group_description(&group.ty).into(),
+                span: lexeme.byte_span,
+            }),
+            LexemeBody::Token(token) => Err(Error::UnexpectedToken {
+                expected,
+                found: format!("`{}`", token.body),
+                span: lexeme.byte_span,
+            }),
+        }
+    }
+
+    fn expect_punct(&mut self, punct: &'static str) -> Result<Span, Error> {
+        if let Some(span) = self.eat_punct(punct) {
+            return Ok(span);
+        }
+        let (token, _, byte_span) = self.next_token(punct)?;
+        Err(Error::UnexpectedToken {
+            expected: punct,
+            found: format!("`{}`", token.body),
+            span: byte_span,
+        })
+    }
+
+    fn expect_ident(&mut self, expected: &'static str) -> Result<Id, Error> {
+        let (token, span, byte_span) = self.next_token(expected)?;
+        if token.ty == TokenType::Id && !token.is_keyword() {
+            Ok(Id {
+                span,
+                value: token.body.clone(),
+            })
+        } else {
+            Err(Error::UnexpectedToken {
+                expected,
+                found: format!("`{}`", token.body),
+                span: byte_span,
+            })
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> Option<Span> {
+        let lexeme = self.peek()?;
+        match &lexeme.body {
+            LexemeBody::Token(token) if token.is_keyword() && token.body == keyword => {
+                self.next();
+                Some(lexeme.span)
+            }
+            _ => None,
+        }
+    }
+
+    /// Eats `punct`, which may be a single character or a known multi-character operator (e.g.
+    /// `"::"`) reassembled from a run of `Joint`-spaced single-char punct lexemes.
+    fn eat_punct(&mut self, punct: &str) -> Option<Span> {
+        let remaining = self.remaining();
+        let count = match_punct(remaining, punct)?;
+        let span = Span {
+            start: remaining[0].span.start,
+            end: remaining[count - 1].span.end,
+        };
+        for _ in 0..count {
+            self.next();
+        }
+        Some(span)
+    }
+}
+
+fn parse_item(cursor: &mut Cursor<'_>) -> Result<Item, Error> {
+    let vis_span = cursor.eat_keyword("pub");
+    let vis = vis_span.map_or(Vis::Priv, Vis::Pub);
+    let (keyword, keyword_span, keyword_byte_span) = cursor.next_token("`fn` or `type`")?;
+    let start = vis_span.map_or(keyword_span.start, |span| span.start);
+    if !keyword.is_keyword() {
+        return Err(Error::UnexpectedToken {
+            expected: "`fn` or `type`",
+            found: format!("`{}`", keyword.body),
+            span: keyword_byte_span,
+        });
+    }
+    let (body, end) = match keyword.body.as_str() {
+        "fn" => {
+            let (f, end) = parse_fn(cursor, vis)?;
+            (ItemBody::Fn(f), end)
+        }
+        "type" => {
+            let (a, end) = parse_alias(cursor, vis)?;
+            (ItemBody::Alias(a), end)
+        }
+        _ => {
+            return Err(Error::UnexpectedToken {
+                expected: "`fn` or `type`",
+                found: format!("`{}`", keyword.body),
+                span: keyword_byte_span,
+            })
+        }
+    };
+    Ok(Item {
+        span: Span { start, end },
+        body,
+    })
+}
+
+fn parse_fn(cursor: &mut Cursor<'_>, vis: Vis) -> Result<(Fn, Pos), Error> {
+    let name = cursor.expect_ident("a function name")?;
+    let (params_body, params_span, _) = cursor.next_group("a parameter list", GroupType::Paren)?;
+    let params = parse_params(params_body)?;
+    Ok((Fn { vis, name, params }, params_span.end))
+}
+
+fn parse_params(lexemes: &[Lexeme]) -> Result<Vec<Param>, Error> {
+    let mut cursor = Cursor::new(lexemes);
+    let mut params = Vec::new();
+    while cursor.peek().is_some() {
+        let name = parse_pattern(&mut cursor)?;
+        cursor.expect_punct(":")?;
+        let ty = parse_type(&mut cursor)?;
+        let span = Span {
+            start: name.span.start,
+            end: ty.span.end,
+        };
+        params.push(Param { span, name, ty });
+        if cursor.eat_punct(",").is_none() {
+            break;
+        }
+    }
+    Ok(params)
+}
+
+fn parse_alias(cursor: &mut Cursor<'_>, vis: Vis) -> Result<(Alias, Pos), Error> {
+    let new = parse_type(cursor)?;
+    cursor.expect_punct("=")?;
+    let under = parse_type(cursor)?;
+    let end = cursor.expect_punct(";")?.end;
+    Ok((Alias { vis, new, under }, end))
+}
+
+fn parse_pattern(cursor: &mut Cursor<'_>) -> Result<Pattern, Error> {
+    let id = cursor.expect_ident("a pattern")?;
+    let span = id.span;
+    Ok(Pattern {
+        span,
+        body: PatternBody::Id(id),
+    })
+}
+
+fn parse_type(cursor: &mut Cursor<'_>) -> Result<Type, Error> {
+    let id = cursor.expect_ident("a type")?;
+    let span = id.span;
+    Ok(Type {
+        span,
+        body: TypeBody::Named(id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lex::lex;
+
+    fn parse_src(src: &str) -> Result<File, Error> {
+        let lexemes = lex(src.chars(), BytePos(0)).expect("lex should succeed");
+        parse_file(&lexemes)
+    }
+
+    fn id_name(pattern: &Pattern) -> &str {
+        let PatternBody::Id(id) = &pattern.body;
+        &id.value
+    }
+
+    fn type_name(ty: &Type) -> &str {
+        let TypeBody::Named(id) = &ty.body;
+        &id.value
+    }
+
+    #[test]
+    fn parses_pub_fn_with_multiple_params() {
+        let file = parse_src("pub fn add(x: Int, y: Int)").expect("parse should succeed");
+        assert_eq!(file.items.len(), 1);
+        let ItemBody::Fn(f) = &file.items[0].body else {
+            panic!("expected a Fn item")
+        };
+        assert!(matches!(f.vis, Vis::Pub(_)));
+        assert_eq!(f.name.value, "add");
+        assert_eq!(f.params.len(), 2);
+        assert_eq!(id_name(&f.params[0].name), "x");
+        assert_eq!(type_name(&f.params[0].ty), "Int");
+        assert_eq!(id_name(&f.params[1].name), "y");
+        assert_eq!(type_name(&f.params[1].ty), "Int");
+    }
+
+    #[test]
+    fn parses_plain_fn_with_no_params() {
+        let file = parse_src("fn noop()").expect("parse should succeed");
+        let ItemBody::Fn(f) = &file.items[0].body else {
+            panic!("expected a Fn item")
+        };
+        assert!(matches!(f.vis, Vis::Priv));
+        assert_eq!(f.name.value, "noop");
+        assert!(f.params.is_empty());
+    }
+
+    #[test]
+    fn parses_type_alias() {
+        let file = parse_src("type Meters = Int;").expect("parse should succeed");
+        let ItemBody::Alias(alias) = &file.items[0].body else {
+            panic!("expected an Alias item")
+        };
+        assert!(matches!(alias.vis, Vis::Priv));
+        assert_eq!(type_name(&alias.new), "Meters");
+        assert_eq!(type_name(&alias.under), "Int");
+    }
+
+    #[test]
+    fn wrong_group_type_errors() {
+        let err = parse_src("fn add[x: Int]").unwrap_err();
+        assert!(matches!(err, Error::UnexpectedToken { expected: "a parameter list", .. }));
+    }
+
+    #[test]
+    fn trailing_tokens_error() {
+        let err = parse_src("fn add() 123").unwrap_err();
+        assert!(matches!(err, Error::UnexpectedToken { expected: "`fn` or `type`", .. }));
+    }
+
+    #[test]
+    fn unexpected_eof_mid_group_errors() {
+        let err = parse_src("fn add(x:)").unwrap_err();
+        assert!(matches!(err, Error::UnexpectedEndOfGroup(_)));
+    }
+
+    #[test]
+    fn eat_punct_reassembles_joint_compound_operator() {
+        let lexemes = lex("->".chars(), BytePos(0)).expect("lex should succeed");
+        let mut cursor = Cursor::new(&lexemes);
+        assert!(cursor.eat_punct("->").is_some());
+        assert!(cursor.peek().is_none());
+    }
+
+    #[test]
+    fn eat_punct_rejects_space_separated_puncts() {
+        let lexemes = lex(": :".chars(), BytePos(0)).expect("lex should succeed");
+        let mut cursor = Cursor::new(&lexemes);
+        assert!(cursor.eat_punct("::").is_none());
+    }
+}
@@ -0,0 +1,115 @@
+use std::cmp::Ordering;
+
+use crate::span::{BytePos, Pos};
+
+/// One file registered with a [`SourceMap`]: its name, full text, and the range it occupies in
+/// the global byte-offset space. `hi` is one byte past this file's last real byte (its EOF
+/// point, e.g. where `Error::Eof` lands) *plus one more* of slack, since every point position is
+/// widened into a one-byte-wide [`crate::span::ByteSpan`] via `BytePos(pos.0 + 1)` even when
+/// `pos` is already the EOF point itself — without the slack, that widened span's `hi` would
+/// land one past every file ever registered and `find_file` would panic on the single most common
+/// error shape (a syntax error at literal end-of-file). [`SourceMap::add_file`] reserves one
+/// further byte beyond `hi` before starting the next file, so this slack can never be mistaken
+/// for the start of whatever comes after it.
+struct SourceFile {
+    name: String,
+    text: String,
+    lo: usize,
+    hi: usize,
+    /// Byte offset (relative to this file's own text) of the start of each line.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    fn new(name: String, text: String, lo: usize) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(
+            text.char_indices()
+                .filter(|&(_, c)| c == '\n')
+                .map(|(i, _)| i + 1),
+        );
+        let hi = lo + text.len() + 1;
+        Self {
+            name,
+            text,
+            lo,
+            hi,
+            line_starts,
+        }
+    }
+
+    fn pos_at(&self, offset: usize) -> Pos {
+        let local = offset - self.lo;
+        let row = match self.line_starts.binary_search(&local) {
+            Ok(row) => row,
+            Err(row) => row - 1,
+        };
+        Pos(row + 1, local - self.line_starts[row] + 1)
+    }
+}
+
+/// Registers every input file in one contiguous global byte-offset space, classic-compiler
+/// style, so a [`BytePos`] recorded while lexing or parsing any one of them can later be traced
+/// back to the file and line/column it came from.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+    next_lo: usize,
+}
+
+impl SourceMap {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            files: Vec::new(),
+            next_lo: 0,
+        }
+    }
+
+    /// Registers a file's full text, returning the [`BytePos`] its content starts at.
+    pub fn add_file(&mut self, name: impl Into<String>, text: impl Into<String>) -> BytePos {
+        let lo = self.next_lo;
+        let text = text.into();
+        let file = SourceFile::new(name.into(), text, lo);
+        self.next_lo = file.hi + 1;
+        self.files.push(file);
+        BytePos(lo)
+    }
+
+    fn find_file(&self, offset: BytePos) -> &SourceFile {
+        let i = self
+            .files
+            .binary_search_by(|file| {
+                if offset.0 < file.lo {
+                    Ordering::Greater
+                } else if offset.0 > file.hi {
+                    Ordering::Less
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .expect("BytePos was not produced by this SourceMap");
+        &self.files[i]
+    }
+
+    /// Looks up which file a [`BytePos`] falls in and its line/column within that file.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` was not produced from a file registered with this map.
+    #[must_use]
+    pub fn lookup(&self, offset: BytePos) -> (&str, Pos) {
+        let file = self.find_file(offset);
+        (&file.name, file.pos_at(offset.0))
+    }
+
+    /// The full source text of the file a [`BytePos`] falls in.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `offset` was not produced from a file registered with this map.
+    #[must_use]
+    pub fn source(&self, offset: BytePos) -> &str {
+        &self.find_file(offset).text
+    }
+}
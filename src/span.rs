@@ -1,6 +1,6 @@
 use std::fmt::{self, Display};
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Pos(pub usize, pub usize); // row, col
 
 impl Display for Pos {
@@ -9,12 +9,18 @@ impl Display for Pos {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Span {
     pub start: Pos,
     pub end: Pos,
 }
 
+impl Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.start)
+    }
+}
+
 impl From<Pos> for Span {
     fn from(pos: Pos) -> Self {
         Self {
@@ -50,3 +56,37 @@ impl From<(Pos, Pos)> for Span {
         }
     }
 }
+
+/// A position in the global byte-offset space spanning every file registered with a
+/// [`crate::source_map::SourceMap`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BytePos(pub usize);
+
+impl Display for BytePos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "byte {}", self.0)
+    }
+}
+
+/// Like [`Span`], but as a range in the global byte-offset space rather than file-local
+/// `(row, col)`, so it can be traced back to its file with [`crate::source_map::SourceMap::lookup`].
+#[derive(Clone, Copy, Debug)]
+pub struct ByteSpan {
+    pub lo: BytePos,
+    pub hi: BytePos,
+}
+
+impl Display for ByteSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}..{}", self.lo.0, self.hi.0)
+    }
+}
+
+impl From<BytePos> for ByteSpan {
+    fn from(pos: BytePos) -> Self {
+        Self {
+            lo: pos,
+            hi: BytePos(pos.0 + 1),
+        }
+    }
+}